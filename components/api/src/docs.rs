@@ -0,0 +1,84 @@
+use actix_web::{HttpResponse, Scope};
+use serde::Deserialize;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use State;
+
+// `chal`/`team`/`user`/`scoreboard` own the real request/response types and
+// don't depend on `utoipa`, so the wire shape is mirrored here purely for
+// documentation. Keep these in sync with their real counterparts by hand.
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct ScoreboardOptionsSchema {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct SubmitFormSchema {
+    flag: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct CreateTeamFormSchema {
+    name: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct InviteUserFormSchema {
+    email: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct LoginFormSchema {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(crate) struct RegisterFormSchema {
+    name: String,
+    email: String,
+    username: String,
+    password: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        ::routes::base::scoreboard,
+        ::routes::chal::list,
+        ::routes::chal::submit,
+        ::routes::team::create,
+        ::routes::team::me,
+        ::routes::team::manage::invite,
+        ::routes::user::login,
+        ::routes::user::register,
+    ),
+    components(schemas(
+        ScoreboardOptionsSchema,
+        SubmitFormSchema,
+        CreateTeamFormSchema,
+        InviteUserFormSchema,
+        LoginFormSchema,
+        RegisterFormSchema,
+    ))
+)]
+struct ApiDoc;
+
+fn openapi_json(_req: &actix_web::HttpRequest<State>) -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+fn swagger_ui(_req: &actix_web::HttpRequest<State>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html")
+        .body(include_str!("../static/swagger_ui.html"))
+}
+
+/// Mounts `GET /docs` (Swagger UI) and `GET /docs/openapi.json` (the generated contract).
+pub fn scope(scope: Scope<State>) -> Scope<State> {
+    scope
+        .resource("", |r| r.f(swagger_ui))
+        .resource("/openapi.json", |r| r.f(openapi_json))
+}
@@ -1,20 +1,66 @@
+use actix_web::middleware::cors::Cors;
+use actix_web::middleware::Compress;
 use actix_web::{App, HttpResponse};
 
 use api::APIMiddleware;
-use State;
+use config::CorsConfig;
+use docs;
+use {ApiError, State};
+
+/// Builds the CORS middleware from `config`, or `None` if cross-origin
+/// requests should not be allowed at all.
+///
+/// actix-web's `Cors` treats "no `allowed_origin` call" as "allow any
+/// origin," so an absent or empty `cors` config must not reach
+/// `Cors::build()` at all -- otherwise every deployment that hasn't
+/// explicitly opted in would go from same-origin-only to fully
+/// cross-origin-open the moment this middleware is installed.
+fn cors(config: Option<&CorsConfig>) -> Option<Cors> {
+    let config = config?;
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+
+    let mut builder = Cors::build();
+    for origin in &config.allowed_origins {
+        builder.allowed_origin(origin);
+    }
+    builder
+        .allowed_methods(config.allowed_methods.iter().map(String::as_str))
+        .allowed_headers(config.allowed_headers.iter().map(String::as_str));
+    Some(builder.finish())
+}
 
 pub fn router(state: State) -> App<State> {
     use team::{middleware::Boolean::*, TeamRequired};
-    use user::LoginRequired;
-    App::with_state(state)
+    use user::{AdminRequired, LoginRequired};
+
+    let cors_config = state.get_web_config().and_then(|web| web.cors.as_ref()).cloned();
+    let cors = cors(cors_config.as_ref());
+
+    let mut app = App::with_state(state)
         .middleware(APIMiddleware)
-        .resource("/", |r| r.f(|_| HttpResponse::Ok().json("hello there")))
+        .middleware(Compress::default());
+    if let Some(cors) = cors {
+        app = app.middleware(cors);
+    }
+
+    app.resource("/", |r| r.f(|_| HttpResponse::Ok().json("hello there")))
         .resource("/scoreboard", |r| r.with(self::base::scoreboard))
         .scope("/chal", |scope| {
             scope
                 .middleware(TeamRequired(False))
                 .resource("/list", |r| r.get().with(self::chal::list))
                 .resource("/submit", |r| r.post().with(self::chal::submit))
+                .nested("/admin", |scope| {
+                    scope
+                        .middleware(AdminRequired)
+                        .resource("/{chal_id}/attachments", |r| {
+                            r.post().with(self::chal::admin::upload)
+                        }).resource("/{chal_id}/attachments/{key}", |r| {
+                            r.get().with(self::chal::admin::download)
+                        })
+                })
         }).scope("/team", |scope| {
             scope
                 .middleware(LoginRequired)
@@ -31,172 +77,309 @@ pub fn router(state: State) -> App<State> {
             scope
                 .resource("/login", |r| r.post().with(self::user::login))
                 .resource("/register", |r| r.post().with(self::user::register))
-        })
+        }).scope("/docs", docs::scope)
 }
 
-mod base {
-    use actix_web::{HttpResponse, Query};
+pub(crate) mod base {
+    use actix_web::{HttpRequest, HttpResponse, Query};
     use scoreboard::{get_scoreboard, ScoreboardOptions};
-    use DbConn;
-
-    pub fn scoreboard((query, db): (Query<ScoreboardOptions>, DbConn)) -> HttpResponse {
-        get_scoreboard(db, &query.into_inner())
-            .map(|entries| {
-                info!("Scoreboard: {:?}", entries);
-                HttpResponse::Ok().json(entries)
-            }).unwrap_or_else(|err| {
-                error!("Error while fetching scoreboard: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+    use docs;
+    use {ApiError, DbConn, State};
+
+    #[utoipa::path(
+        get,
+        path = "/scoreboard",
+        params(docs::ScoreboardOptionsSchema),
+        responses((status = 200, description = "Scoreboard entries, most points first"))
+    )]
+    pub fn scoreboard(
+        (req, query, db): (HttpRequest<State>, Query<ScoreboardOptions>, DbConn),
+    ) -> Result<HttpResponse, ApiError> {
+        let entries = get_scoreboard(db, &query.into_inner())?;
+        info!("Scoreboard: {:?}", entries);
+
+        let mut entries = ::serde_json::to_value(entries)?;
+        req.state().get_ids().encode_value(&mut entries);
+        Ok(HttpResponse::Ok().json(entries))
     }
 }
 
-mod chal {
-    use actix_web::{HttpResponse, Json};
+pub(crate) mod chal {
+    use actix_web::{HttpRequest, HttpResponse, Json};
     use chal::{list_all, submit_flag, Submission, SubmitForm};
-    use DbConn;
-
-    pub fn list(db: DbConn) -> HttpResponse {
-        list_all(db)
-            .map(|chals| {
-                HttpResponse::Ok().json(
-                    chals
-                        .iter()
-                        .map(|chal| {
-                            json!({
-                                "title": chal.title,
-                                "value": chal.value,
-                                "description": chal.description,
-                            })
-                        }).collect::<Vec<_>>(),
-                )
-            }).unwrap_or_else(|err| {
-                error!("Error while listing chals: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+    use docs;
+    use {ApiError, DbConn, State};
+
+    #[utoipa::path(
+        get,
+        path = "/chal/list",
+        responses((status = 200, description = "All challenges, with downloadable attachment URLs"))
+    )]
+    pub fn list((req, db): (HttpRequest<State>, DbConn)) -> Result<HttpResponse, ApiError> {
+        let ids = req.state().get_ids();
+        let chals = list_all(db)?;
+        Ok(HttpResponse::Ok().json(
+            chals
+                .iter()
+                .map(|chal| {
+                    json!({
+                        "id": ids.encode(chal.id),
+                        "title": chal.title,
+                        "value": chal.value,
+                        "description": chal.description,
+                        "attachments": chal.attachments
+                            .iter()
+                            .map(|attachment| &attachment.url)
+                            .collect::<Vec<_>>(),
+                    })
+                }).collect::<Vec<_>>(),
+        ))
     }
 
-    pub fn submit((form, db): (Json<SubmitForm>, DbConn)) -> HttpResponse {
+    #[utoipa::path(
+        post,
+        path = "/chal/submit",
+        request_body = docs::SubmitFormSchema,
+        responses(
+            (status = 200, description = "Whether the submitted flag was correct"),
+            (status = 401, description = "No team selected")
+        )
+    )]
+    pub fn submit(
+        (form, db): (Json<SubmitForm>, DbConn),
+    ) -> Result<HttpResponse, ApiError> {
         let form = form.into_inner();
         let submission = Submission {
             user_id: 1,
             team_id: 1,
             form,
         };
-        submit_flag(db, submission)
-            .map(|result| HttpResponse::Ok().json(result))
-            .unwrap_or_else(|err| {
-                error!("Error during submission: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+        let result = submit_flag(db, submission)?;
+        Ok(HttpResponse::Ok().json(result))
+    }
+
+    pub mod admin {
+        use std::path::Path;
+
+        use actix_web::multipart::MultipartItem;
+        use actix_web::{AsyncResponder, FutureResponse, HttpMessage, HttpRequest, HttpResponse};
+        use failure::Error;
+        use futures::future;
+        use futures::{Future, Stream};
+
+        use chal::add_attachment;
+        use {ApiError, DbConn, State};
+
+        pub fn upload((req, db): (HttpRequest<State>, DbConn)) -> FutureResponse<HttpResponse> {
+            let state = req.state().clone();
+            let chal_id = match req
+                .match_info()
+                .get("chal_id")
+                .and_then(|public_id| state.get_ids().decode(public_id))
+            {
+                Some(id) => id,
+                None => return Box::new(future::err(ApiError::NotFound)),
+            };
+
+            req.multipart()
+                .map_err(ApiError::from)
+                .and_then(move |field| store_field(field, state.clone(), db.clone(), chal_id))
+                .collect()
+                .map(|attachments| HttpResponse::Ok().json(attachments))
+                .responder()
+        }
+
+        fn store_field(
+            field: MultipartItem<::actix_web::dev::Payload>,
+            state: State,
+            db: DbConn,
+            chal_id: i32,
+        ) -> Box<Future<Item = ::serde_json::Value, Error = ApiError>> {
+            let field = match field {
+                MultipartItem::Field(field) => field,
+                MultipartItem::Nested(_) => {
+                    return Box::new(future::err(ApiError::BadRequest(
+                        "nested multipart is not supported".into(),
+                    )))
+                }
+            };
+            // Only the basename survives: a client-supplied filename like
+            // `../../../etc/passwd` must not be able to steer `key` outside
+            // the configured storage root.
+            let filename = field
+                .content_disposition()
+                .and_then(|cd| cd.get_filename().map(str::to_owned))
+                .and_then(|name| {
+                    Path::new(&name)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                }).unwrap_or_else(|| "attachment".to_owned());
+            let key = format!("{}/{}", state.get_ids().encode(chal_id), filename);
+
+            let store = match state.get_filestore() {
+                Some(store) => store,
+                None => {
+                    return Box::new(future::err(ApiError::BadRequest(
+                        "no filestore configured".into(),
+                    )))
+                }
+            };
+            let body: Box<Stream<Item = _, Error = Error>> =
+                Box::new(field.map_err(|err| format_err!("{}", err)));
+
+            Box::new(
+                store
+                    .store(&key, body)
+                    .map_err(ApiError::from)
+                    .and_then(move |url| {
+                        add_attachment(&db, chal_id, &filename, &url)?;
+                        Ok(json!({ "name": filename, "url": url }))
+                    }),
+            )
+        }
+
+        pub fn download(req: HttpRequest<State>) -> Result<HttpResponse, ApiError> {
+            let chal_id = req
+                .match_info()
+                .get("chal_id")
+                .and_then(|public_id| req.state().get_ids().decode(public_id))
+                .ok_or(ApiError::NotFound)?;
+            let filename = req.match_info().get("key").ok_or(ApiError::NotFound)?;
+            let key = format!("{}/{}", req.state().get_ids().encode(chal_id), filename);
+
+            let store = req.state().get_filestore().ok_or(ApiError::NotFound)?;
+            let stream = store.fetch(&key)?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/octet-stream")
+                .streaming(stream.map_err(::actix_web::error::ErrorInternalServerError)))
+        }
     }
 }
 
-mod team {
+pub(crate) mod team {
     use actix_web::{HttpRequest, HttpResponse, Json};
     use team::{create_team, my_profile, CreateTeamForm};
     use user::auth::LoginClaims;
-    use {DbConn, State};
+    use docs;
+    use {ApiError, DbConn, State};
 
+    #[utoipa::path(
+        post,
+        path = "/team/create",
+        request_body = docs::CreateTeamFormSchema,
+        responses((status = 200, description = "Team created"))
+    )]
     pub fn create(
         (req, form, db): (HttpRequest<State>, Json<CreateTeamForm>, DbConn),
-    ) -> HttpResponse {
+    ) -> Result<HttpResponse, ApiError> {
         let ext = req.extensions();
         let claims = ext.get::<LoginClaims>().unwrap();
         let form = form.into_inner();
-        create_team(db, claims.id, form)
-            .map(|_| HttpResponse::Ok().finish())
-            .unwrap_or_else(|err| {
-                error!("Error during team creation: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+        create_team(db, claims.id, form)?;
+        Ok(HttpResponse::Ok().finish())
     }
 
-    pub fn me((req, db): (HttpRequest<State>, DbConn)) -> HttpResponse {
+    #[utoipa::path(
+        get,
+        path = "/team/me",
+        responses((status = 200, description = "The logged-in user's team profile"))
+    )]
+    pub fn me((req, db): (HttpRequest<State>, DbConn)) -> Result<HttpResponse, ApiError> {
         let ext = req.extensions();
         let claims = ext.get::<LoginClaims>().unwrap();
 
-        my_profile(db, claims.id)
-            .map(|profile| HttpResponse::Ok().json(profile))
-            .unwrap_or_else(|err| {
-                error!("Error fetching profile: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+        let profile = my_profile(db, claims.id)?;
+        let mut profile = ::serde_json::to_value(profile)?;
+        req.state().get_ids().encode_value(&mut profile);
+        Ok(HttpResponse::Ok().json(profile))
     }
 
-    pub fn accept(_db: DbConn) -> HttpResponse {
+    pub fn accept(_db: DbConn) -> Result<HttpResponse, ApiError> {
         // TODO: finish this
-        HttpResponse::Ok().finish()
+        Ok(HttpResponse::Ok().finish())
     }
 
     pub mod manage {
         use actix_web::{HttpResponse, Json};
         use team::manage::{invite_user, InviteUserForm};
-        use DbConn;
+        use docs;
+        use {ApiError, DbConn};
 
-        pub fn invite((form, db): (Json<InviteUserForm>, DbConn)) -> HttpResponse {
+        #[utoipa::path(
+            post,
+            path = "/team/manage/invite",
+            request_body = docs::InviteUserFormSchema,
+            responses((status = 200, description = "User invited to the team"))
+        )]
+        pub fn invite(
+            (form, db): (Json<InviteUserForm>, DbConn),
+        ) -> Result<HttpResponse, ApiError> {
             let form = form.into_inner();
-            invite_user(db, form)
-                .map(|_| HttpResponse::Ok().finish())
-                .unwrap_or_else(|err| {
-                    error!("Error inviting user: {}", err);
-                    HttpResponse::InternalServerError().finish()
-                })
+            invite_user(db, form)?;
+            Ok(HttpResponse::Ok().finish())
         }
 
-        pub fn kick(_db: DbConn) -> HttpResponse {
+        pub fn kick(_db: DbConn) -> Result<HttpResponse, ApiError> {
             // TODO: finish this
-            HttpResponse::Ok().finish()
+            Ok(HttpResponse::Ok().finish())
         }
     }
 }
 
-mod user {
+pub(crate) mod user {
     use actix_web::{HttpRequest, HttpResponse, Json};
-    use user::auth::{login_user, register_user, LoginForm, RegisterForm, UserError};
-    use {DbConn, State};
+    use user::auth::{login_user, register_user, LoginForm, RegisterForm};
+    use docs;
+    use {ApiError, DbConn, State};
 
-    pub fn login((req, form, db): (HttpRequest<State>, Json<LoginForm>, DbConn)) -> HttpResponse {
+    #[utoipa::path(
+        post,
+        path = "/user/login",
+        request_body = docs::LoginFormSchema,
+        responses(
+            (status = 200, description = "JWT for the session"),
+            (status = 401, description = "Bad username or password")
+        )
+    )]
+    pub fn login(
+        (req, form, db): (HttpRequest<State>, Json<LoginForm>, DbConn),
+    ) -> Result<HttpResponse, ApiError> {
         let state = req.state();
         let form = form.into_inner();
 
         info!("Login request: email={:?}", form.email);
-        login_user(db, state.get_secret_key(), form)
-            .map(|(user, token)| {
-                info!(
-                    "Successfully logged in: id={:?}, email={:?}",
-                    user.id, user.email
-                );
-                HttpResponse::Ok().json(token)
-            }).unwrap_or_else(|err| match err {
-                UserError::AlreadyRegistered => HttpResponse::BadRequest().finish(),
-                UserError::BadUsernameOrPassword => HttpResponse::Unauthorized().finish(),
-                UserError::ServerError(err) => {
-                    error!("Error logging in: {}", err);
-                    HttpResponse::InternalServerError().finish()
-                }
-            })
+        let (user, token) = login_user(db, state.get_secret_key(), form)?;
+        info!(
+            "Successfully logged in: id={:?}, email={:?}",
+            user.id, user.email
+        );
+        Ok(HttpResponse::Ok().json(token))
     }
 
+    #[utoipa::path(
+        post,
+        path = "/user/register",
+        request_body = docs::RegisterFormSchema,
+        responses(
+            (status = 200, description = "JWT for the new session"),
+            (status = 409, description = "Email already registered")
+        )
+    )]
     pub fn register(
         (req, form, db): (HttpRequest<State>, Json<RegisterForm>, DbConn),
-    ) -> HttpResponse {
+    ) -> Result<HttpResponse, ApiError> {
         let state = req.state();
         let form = form.into_inner();
         info!(
             "Register request: username={:?}, email={:?}",
             form.username, form.email
         );
-        register_user(db, state.get_secret_key(), form)
-            .map(|(user, token)| {
-                info!(
-                    "Successfully registered: id={:?}, username={:?}",
-                    user.id, user.name
-                );
-                HttpResponse::Ok().json(token)
-            }).unwrap_or_else(|err| {
-                error!("Error registering: {}", err);
-                HttpResponse::InternalServerError().finish()
-            })
+        let (user, token) = register_user(db, state.get_secret_key(), form)?;
+        info!(
+            "Successfully registered: id={:?}, username={:?}",
+            user.id, user.name
+        );
+        Ok(HttpResponse::Ok().json(token))
     }
 }
@@ -0,0 +1,118 @@
+//! Challenge catalog: listings, flag submission, and file attachments.
+
+extern crate db;
+extern crate failure;
+#[macro_use]
+extern crate diesel;
+#[macro_use]
+extern crate serde_derive;
+
+use diesel::prelude::*;
+use failure::Error;
+
+use db::DbConn;
+
+table! {
+    chals (id) {
+        id -> Integer,
+        title -> Varchar,
+        value -> Integer,
+        description -> Text,
+        flag -> Varchar,
+    }
+}
+
+table! {
+    attachments (id) {
+        id -> Integer,
+        chal_id -> Integer,
+        name -> Varchar,
+        url -> Varchar,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Queryable)]
+pub struct Attachment {
+    pub id: i32,
+    pub chal_id: i32,
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Queryable)]
+struct ChalRow {
+    id: i32,
+    title: String,
+    value: i32,
+    description: String,
+    flag: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Challenge {
+    pub id: i32,
+    pub title: String,
+    pub value: i32,
+    pub description: String,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitForm {
+    pub flag: String,
+}
+
+pub struct Submission {
+    pub user_id: i32,
+    pub team_id: i32,
+    pub form: SubmitForm,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitResult {
+    pub correct: bool,
+}
+
+/// Lists every challenge along with its attachments.
+pub fn list_all(conn: DbConn) -> Result<Vec<Challenge>, Error> {
+    let rows = chals::table.load::<ChalRow>(&*conn)?;
+    let all_attachments = attachments::table.load::<Attachment>(&*conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let attachments = all_attachments
+                .iter()
+                .filter(|attachment| attachment.chal_id == row.id)
+                .cloned()
+                .collect();
+
+            Challenge {
+                id: row.id,
+                title: row.title,
+                value: row.value,
+                description: row.description,
+                attachments,
+            }
+        }).collect())
+}
+
+pub fn submit_flag(conn: DbConn, submission: Submission) -> Result<SubmitResult, Error> {
+    let correct = chals::table
+        .filter(chals::flag.eq(&submission.form.flag))
+        .first::<ChalRow>(&*conn)
+        .optional()?
+        .is_some();
+    Ok(SubmitResult { correct })
+}
+
+/// Records a file attachment against `chal_id`, returning once the insert commits.
+pub fn add_attachment(conn: &DbConn, chal_id: i32, name: &str, url: &str) -> Result<(), Error> {
+    diesel::insert_into(attachments::table)
+        .values((
+            attachments::chal_id.eq(chal_id),
+            attachments::name.eq(name),
+            attachments::url.eq(url),
+        )).execute(&**conn)?;
+    Ok(())
+}
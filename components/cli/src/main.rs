@@ -0,0 +1,142 @@
+extern crate chal;
+extern crate clap;
+extern crate config;
+extern crate db;
+extern crate diesel;
+extern crate failure;
+extern crate rpassword;
+extern crate team;
+extern crate user;
+
+use clap::{Parser, Subcommand};
+use diesel::Connection as _;
+use failure::Error;
+
+use chal::{create_challenge, ChallengeForm};
+use config::Config;
+use db::{establish_connection, Connection};
+use team::{create_team, CreateTeamForm};
+use user::auth::{grant_admin, list_users, register_user, RegisterForm};
+
+#[derive(Parser)]
+#[clap(name = "librectf-admin", about = "Offline admin tool for librectf")]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a user directly, bypassing the HTTP registration flow.
+    CreateUser {
+        name: String,
+        email: String,
+        username: String,
+        #[clap(long)]
+        password: Option<String>,
+        /// Also create a solo team named after the username, atomically with
+        /// the user. Useful for bootstrapping the first account.
+        #[clap(long)]
+        with_team: bool,
+    },
+    /// List every registered user.
+    ListUsers,
+    /// Create a challenge.
+    CreateChallenge {
+        title: String,
+        description: String,
+        #[clap(long)]
+        value: i32,
+        #[clap(long)]
+        flag: String,
+    },
+    /// Promote an existing user to admin.
+    GrantAdmin { username: String },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let config = Config::load().expect("failed to load config");
+    let pool = establish_connection(&config.database_url);
+    let conn = pool.get().expect("failed to get a database connection");
+
+    let result = match cli.command {
+        Command::CreateUser {
+            name,
+            email,
+            username,
+            password,
+            with_team,
+        } => create_user(&conn, &config, name, email, username, password, with_team),
+        Command::ListUsers => list_users(&conn).map(|users| {
+            for user in users {
+                println!("{}\t{}\t{}", user.id, user.username, user.email);
+            }
+        }),
+        Command::CreateChallenge {
+            title,
+            description,
+            value,
+            flag,
+        } => create_challenge(
+            &conn,
+            ChallengeForm {
+                title,
+                description,
+                value,
+                flag,
+            },
+        ).map(|chal| println!("Created challenge #{}: {}", chal.id, chal.title)),
+        Command::GrantAdmin { username } => grant_admin(&conn, &username),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn create_user(
+    conn: &Connection,
+    config: &Config,
+    name: String,
+    email: String,
+    username: String,
+    password: Option<String>,
+    with_team: bool,
+) -> Result<(), Error> {
+    let password = match password {
+        Some(password) => password,
+        None => rpassword::prompt_password_stdout("Password: ")?,
+    };
+
+    let form = RegisterForm {
+        name,
+        email,
+        username,
+        password,
+    };
+
+    // Run the user insert and the (opt-in) team insert in one transaction:
+    // if `create_team` fails (e.g. a name collision), the user insert rolls
+    // back with it instead of leaving behind a teamless user that `--with-team`
+    // has no way to fix up afterwards.
+    let user = conn.transaction::<_, Error, _>(|| {
+        let (user, _) = register_user(conn, config.get_secret_key(), form)?;
+
+        if with_team {
+            create_team(
+                conn,
+                user.id,
+                CreateTeamForm {
+                    name: user.username.clone(),
+                },
+            )?;
+        }
+
+        Ok(user)
+    })?;
+    println!("Created user #{}: {}", user.id, user.username);
+
+    Ok(())
+}
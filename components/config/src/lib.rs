@@ -0,0 +1,45 @@
+//! Deserialized application configuration.
+
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub database_url: String,
+    pub web: Option<WebConfig>,
+    pub ids: IdsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebConfig {
+    pub filestore: Option<FilestoreConfig>,
+
+    /// Directory of `.html` templates to glob and load at startup; hot-reloaded
+    /// in debug builds (see `core::templates::watch`).
+    pub templates_dir: Option<String>,
+
+    /// CORS policy for the API. Absent (or an empty `allowed_origins`) means
+    /// no cross-origin requests are allowed at all -- see `api::routes::cors`.
+    pub cors: Option<CorsConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilestoreConfig {
+    pub path: String,
+    pub base_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+}
+
+/// Configures the sqids alphabet/padding used to obfuscate database row IDs
+/// on the wire (see `core::ids::IdCodec`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdsConfig {
+    pub alphabet: String,
+    pub min_length: u8,
+}
@@ -0,0 +1,104 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use failure::Error as FailureError;
+use r2d2::Error as PoolError;
+use tera::Error as TemplateError;
+use thiserror::Error;
+
+use user::auth::UserError;
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("database error: {0}")]
+    Database(DieselError),
+
+    #[error("database connection error: {0}")]
+    Pool(#[from] PoolError),
+
+    #[error("template error: {0}")]
+    Template(#[from] TemplateError),
+
+    #[error("internal error: {0}")]
+    Internal(#[from] FailureError),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] ::serde_json::Error),
+
+    #[error("not found")]
+    NotFound,
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("bad request: {0}")]
+    BadRequest(String),
+
+    #[error("conflict")]
+    Conflict,
+}
+
+// Diesel returns `NotFound` from plain by-id lookups (`.first()`,
+// `.get_result()`); that's a 404, not a database failure, so it gets its
+// own branch instead of going through a blanket `#[from]`.
+impl From<DieselError> for ApiError {
+    fn from(err: DieselError) -> ApiError {
+        match err {
+            DieselError::NotFound => ApiError::NotFound,
+            err => ApiError::Database(err),
+        }
+    }
+}
+
+impl From<UserError> for ApiError {
+    fn from(err: UserError) -> ApiError {
+        match err {
+            UserError::AlreadyRegistered => ApiError::Conflict,
+            UserError::BadUsernameOrPassword => ApiError::Unauthorized,
+            UserError::ServerError(err) => ApiError::from(err),
+        }
+    }
+}
+
+impl ApiError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            ApiError::Database(_)
+            | ApiError::Pool(_)
+            | ApiError::Template(_)
+            | ApiError::Internal(_)
+            | ApiError::Serialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Conflict => StatusCode::CONFLICT,
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        // Log every 5xx cause server-side; none of their `Display` text is
+        // safe or useful to hand back to the client.
+        let message = if status.is_server_error() {
+            match *self {
+                ApiError::Database(ref err) => error!("Database error: {}", err),
+                ApiError::Pool(ref err) => error!("Database connection error: {}", err),
+                ApiError::Template(ref err) => error!("Template error: {}", err),
+                ApiError::Internal(ref err) => error!("Internal error: {}", err),
+                ApiError::Serialization(ref err) => error!("Serialization error: {}", err),
+                _ => unreachable!("status_code() only maps these variants to a 5xx"),
+            }
+            "internal server error".to_owned()
+        } else {
+            self.to_string()
+        };
+
+        HttpResponse::build(status).json(json!({
+            "status": status.as_u16(),
+            "message": message,
+        }))
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use failure::Error;
+use futures::{future, Future, Stream};
+
+use config::FilestoreConfig;
+
+/// A place challenge attachments are persisted and served from.
+///
+/// Implementations are free to store bytes however they like (disk, S3,
+/// ...); callers only ever see a `key` and the `url` it resolves to.
+pub trait FileStore: Send + Sync {
+    /// Persist `body` under `key` as it streams in, returning the URL it can
+    /// be downloaded from. Implementations must not buffer the whole body
+    /// into memory before writing it.
+    fn store(
+        &self,
+        key: &str,
+        body: Box<Stream<Item = Bytes, Error = Error>>,
+    ) -> Box<Future<Item = String, Error = Error>>;
+
+    /// Stream the bytes previously stored under `key`.
+    fn fetch(&self, key: &str) -> Result<Box<Stream<Item = Bytes, Error = Error>>, Error>;
+
+    /// Remove the file stored under `key`, if any.
+    fn delete(&self, key: &str) -> Result<(), Error>;
+}
+
+/// Stores attachments as plain files under a configured directory on disk.
+pub struct LocalDiskStore {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalDiskStore {
+    pub fn new(config: &FilestoreConfig) -> Result<LocalDiskStore, Error> {
+        Self::at(
+            PathBuf::from(&config.path),
+            config.base_url.trim_right_matches('/').to_owned(),
+        )
+    }
+
+    fn at(root: PathBuf, base_url: String) -> Result<LocalDiskStore, Error> {
+        fs::create_dir_all(&root)?;
+        Ok(LocalDiskStore { root, base_url })
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+impl FileStore for LocalDiskStore {
+    fn store(
+        &self,
+        key: &str,
+        body: Box<Stream<Item = Bytes, Error = Error>>,
+    ) -> Box<Future<Item = String, Error = Error>> {
+        let path = self.path_for(key);
+        let url = format!("{}/{}", self.base_url, key);
+
+        Box::new(
+            future::result(
+                path.parent()
+                    .map(fs::create_dir_all)
+                    .unwrap_or(Ok(()))
+                    .and_then(|_| fs::File::create(&path))
+                    .map_err(Error::from),
+            ).and_then(move |file| {
+                body.fold(file, |mut file, chunk| match file.write_all(&chunk) {
+                    Ok(()) => future::ok(file),
+                    Err(err) => future::err(Error::from(err)),
+                })
+            }).map(move |_file| url),
+        )
+    }
+
+    fn fetch(&self, key: &str) -> Result<Box<Stream<Item = Bytes, Error = Error>>, Error> {
+        let bytes = fs::read(self.path_for(key))?;
+        Ok(Box::new(futures::stream::once(Ok(Bytes::from(bytes)))))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Error> {
+        fs::remove_file(self.path_for(key))?;
+        Ok(())
+    }
+}
+
+/// Builds the `FileStore` selected by the given config.
+pub fn from_config(config: &FilestoreConfig) -> Result<Box<FileStore>, Error> {
+    Ok(Box::new(LocalDiskStore::new(config)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::stream;
+
+    use super::*;
+
+    fn test_store(name: &str) -> LocalDiskStore {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let root = std::env::temp_dir().join(format!("librectf-filestore-test-{}-{}", name, n));
+        let _ = fs::remove_dir_all(&root);
+
+        LocalDiskStore::at(root, "http://localhost/attachments".to_owned()).unwrap()
+    }
+
+    #[test]
+    fn store_then_fetch_round_trips() {
+        let store = test_store("round-trip");
+        let body: Box<Stream<Item = Bytes, Error = Error>> = Box::new(stream::iter_ok(vec![
+            Bytes::from_static(b"hello, "),
+            Bytes::from_static(b"world"),
+        ]));
+
+        let url = store.store("chal/1/attachment.txt", body).wait().unwrap();
+        assert_eq!(url, "http://localhost/attachments/chal/1/attachment.txt");
+
+        let fetched = store
+            .fetch("chal/1/attachment.txt")
+            .unwrap()
+            .concat2()
+            .wait()
+            .unwrap();
+        assert_eq!(&fetched[..], b"hello, world");
+
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+
+    #[test]
+    fn store_creates_missing_parent_directories() {
+        let store = test_store("missing-parent");
+        let body: Box<Stream<Item = Bytes, Error = Error>> =
+            Box::new(stream::iter_ok(vec![Bytes::from_static(b"data")]));
+
+        store
+            .store("does/not/exist/yet.txt", body)
+            .wait()
+            .expect("store should create intermediate directories");
+
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+
+    #[test]
+    fn delete_removes_stored_file() {
+        let store = test_store("delete");
+        let body: Box<Stream<Item = Bytes, Error = Error>> =
+            Box::new(stream::iter_ok(vec![Bytes::from_static(b"bye")]));
+
+        store.store("f.txt", body).wait().unwrap();
+        store.delete("f.txt").unwrap();
+        assert!(store.fetch("f.txt").is_err());
+
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use failure::Error;
+use serde_json::Value;
+use sqids::Sqids;
+
+use config::Config;
+
+/// JSON object keys treated as raw database IDs and rewritten in place by
+/// [`IdCodec::encode_value`].
+const ID_FIELDS: &[&str] = &["id", "user_id", "team_id", "chal_id"];
+
+/// Encodes/decodes database row IDs into short, non-enumerable public IDs.
+///
+/// Keeps raw primary keys off the wire so competitors can't scrape the
+/// platform by walking sequential integers or infer team/user counts from
+/// them; the integer key only ever exists inside the DB boundary.
+pub struct IdCodec {
+    sqids: Sqids,
+}
+
+impl IdCodec {
+    pub fn from_config(config: &Config) -> Result<IdCodec, Error> {
+        let sqids = Sqids::builder()
+            .alphabet(config.ids.alphabet.chars().collect())
+            .min_length(config.ids.min_length)
+            .build()
+            .map_err(|err| format_err!("Invalid sqids alphabet: {}", err))?;
+
+        Ok(IdCodec { sqids })
+    }
+
+    pub fn encode(&self, id: i32) -> String {
+        // A raw integer ID is exactly what this type exists to hide, so a
+        // failed encode must not silently fall back to `id.to_string()` --
+        // that would leak the sequential key this feature is meant to mask.
+        // `Sqids::encode` only errs on a misconfigured alphabet, which
+        // `from_config` already validates at startup, so this should be
+        // unreachable in practice.
+        self.sqids
+            .encode(&[id as u64])
+            .unwrap_or_else(|err| panic!("failed to encode id {}: {}", id, err))
+    }
+
+    pub fn decode(&self, public_id: &str) -> Option<i32> {
+        let decoded = self.sqids.decode(public_id);
+        match decoded.as_slice() {
+            [id] => Some(*id as i32),
+            _ => None,
+        }
+    }
+
+    /// Walks a serialized response and rewrites every field in [`ID_FIELDS`]
+    /// from a raw integer into its encoded public ID.
+    ///
+    /// Handlers that hand a real `chal`/`team`/`user`/`scoreboard` struct
+    /// straight to `HttpResponse::Ok().json(..)` don't have an encoded ID to
+    /// put there in the first place; serializing to a `Value` first and
+    /// rewriting the known ID fields here keeps those home crates free of a
+    /// `core` dependency while still ensuring no sequential ID reaches the
+    /// wire unencoded.
+    pub fn encode_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, val) in map.iter_mut() {
+                    if ID_FIELDS.contains(&key.as_str()) {
+                        if let Some(id) = val.as_i64() {
+                            *val = Value::String(self.encode(id as i32));
+                            continue;
+                        }
+                    }
+                    self.encode_value(val);
+                }
+            }
+            Value::Array(items) => {
+                for item in items.iter_mut() {
+                    self.encode_value(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec() -> IdCodec {
+        IdCodec {
+            sqids: Sqids::builder().build().unwrap(),
+        }
+    }
+
+    #[test]
+    fn encode_then_decode_round_trips() {
+        let codec = codec();
+        for id in &[0, 1, 42, 1_000_000] {
+            let encoded = codec.encode(*id);
+            assert_eq!(codec.decode(&encoded), Some(*id));
+        }
+    }
+
+    #[test]
+    fn decode_rejects_garbage() {
+        let codec = codec();
+        assert_eq!(codec.decode("not a real public id"), None);
+    }
+
+    #[test]
+    fn distinct_ids_encode_to_distinct_strings() {
+        let codec = codec();
+        assert_ne!(codec.encode(1), codec.encode(2));
+    }
+
+    #[test]
+    fn encode_value_rewrites_known_id_fields_recursively() {
+        let codec = codec();
+        let mut value = json!({
+            "id": 7,
+            "name": "some team",
+            "members": [
+                {"user_id": 1, "name": "alice"},
+                {"user_id": 2, "name": "bob"},
+            ],
+        });
+
+        codec.encode_value(&mut value);
+
+        assert_eq!(value["id"], json!(codec.encode(7)));
+        assert_eq!(value["members"][0]["user_id"], json!(codec.encode(1)));
+        assert_eq!(value["members"][1]["user_id"], json!(codec.encode(2)));
+        assert_eq!(value["name"], json!("some team"));
+    }
+}
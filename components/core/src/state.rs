@@ -5,9 +5,13 @@ use tera::{Context, Tera};
 
 use config::{Config, FilestoreConfig, WebConfig};
 use db::{establish_connection, Connection, Pool};
+use filestore::{self, FileStore};
+use ids::IdCodec;
 
 struct InnerState {
     pub(super) db_pool: Pool,
+    filestore: Option<Box<FileStore>>,
+    ids: IdCodec,
 }
 
 #[derive(Clone)]
@@ -20,18 +24,68 @@ pub struct State {
 impl State {
     pub fn from(config: &Config) -> State {
         let db_pool = establish_connection(&config.database_url);
+        let ids = IdCodec::from_config(config).expect("invalid id codec config");
+        let filestore = config
+            .web
+            .as_ref()
+            .and_then(|web| web.filestore.as_ref())
+            .and_then(|cfg| match filestore::from_config(cfg) {
+                Ok(store) => Some(store),
+                Err(err) => {
+                    error!("Failed to initialize filestore: {}", err);
+                    None
+                }
+            });
 
-        let inner = Arc::new(InnerState { db_pool });
+        let inner = Arc::new(InnerState {
+            db_pool,
+            filestore,
+            ids,
+        });
         let config = Arc::new(config.clone());
-        let tera = Arc::new(Mutex::new(Tera::default()));
+        let tera = Arc::new(Mutex::new(Self::load_templates(&config)));
 
-        State {
+        let state = State {
             inner,
             config,
             tera,
+        };
+
+        #[cfg(debug_assertions)]
+        {
+            if let Some(dir) = state.get_web_config().and_then(|web| web.templates_dir.as_ref()) {
+                ::templates::watch(state.clone(), dir);
+            }
+        }
+
+        state
+    }
+
+    fn load_templates(config: &Config) -> Tera {
+        match config.web.as_ref().and_then(|web| web.templates_dir.as_ref()) {
+            Some(dir) => {
+                Tera::new(&format!("{}/**/*.html", dir)).unwrap_or_else(|err| {
+                    error!("Failed to load templates from {}: {}", dir, err);
+                    Tera::default()
+                })
+            }
+            None => Tera::default(),
         }
     }
 
+    /// Re-globs `templates_dir` and reloads every template from disk, so
+    /// edits show up without restarting the server. Only wired up in debug
+    /// builds; release builds should bake templates in at startup.
+    #[cfg(debug_assertions)]
+    pub fn reload_templates(&self) -> Result<(), Error> {
+        let mut t = self
+            .tera
+            .lock()
+            .map_err(|err| format_err!("Internal error acquiring Tera lock: {}", err))?;
+        t.full_reload()
+            .map_err(|err| format_err!("Error reloading Tera templates: {}", err))
+    }
+
     pub fn get_web_config(&self) -> Option<&WebConfig> {
         self.config.web.as_ref()
     }
@@ -40,6 +94,14 @@ impl State {
         self.get_web_config().and_then(|cfg| cfg.filestore.as_ref())
     }
 
+    pub fn get_filestore(&self) -> Option<&FileStore> {
+        self.inner.filestore.as_ref().map(|store| store.as_ref())
+    }
+
+    pub fn get_ids(&self) -> &IdCodec {
+        &self.inner.ids
+    }
+
     pub fn render(&self, page: impl AsRef<str>, ctx: &Context) -> Result<String, Error> {
         let t = self
             .tera
@@ -0,0 +1,41 @@
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use notify::{watcher, RecursiveMode, Watcher};
+
+use State;
+
+/// Spawns a background thread that watches `templates_dir` for changes and
+/// calls `State::reload_templates()` whenever something under it is
+/// touched, so edits to server-rendered pages show up without a restart.
+///
+/// Debug builds only; `State::reload_templates` doesn't exist in release.
+#[cfg(debug_assertions)]
+pub fn watch(state: State, templates_dir: impl AsRef<Path>) {
+    let templates_dir = templates_dir.as_ref().to_owned();
+
+    thread::spawn(move || {
+        let (tx, rx) = channel();
+        let mut watcher = match watcher(tx, Duration::from_millis(500)) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                error!("Failed to start template watcher: {}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = watcher.watch(&templates_dir, RecursiveMode::Recursive) {
+            error!("Failed to watch {}: {}", templates_dir.display(), err);
+            return;
+        }
+
+        for event in rx {
+            debug!("Template change detected: {:?}", event);
+            if let Err(err) = state.reload_templates() {
+                error!("Failed to reload templates: {}", err);
+            }
+        }
+    });
+}